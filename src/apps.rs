@@ -1,6 +1,10 @@
 use std::{borrow::Cow, fmt};
 
+use base64;
+use rand;
+use reqwest;
 use try_from::TryInto;
+use url;
 
 use errors::{Error, Result};
 
@@ -20,7 +24,40 @@ impl App {
     }
 
     pub fn scopes(&self) -> Scopes {
-        self.scopes
+        self.scopes.clone()
+    }
+
+    /// Registers this `App` with the instance at `base_url`, returning the
+    /// `client_id`/`client_secret` credentials needed to carry out the OAuth
+    /// token exchange.
+    ///
+    /// ```no_run
+    /// use elefren::apps::App;
+    ///
+    /// # fn main() -> Result<(), Box<::std::error::Error>> {
+    /// let mut builder = App::builder();
+    /// builder.client_name("elefren_test");
+    /// let app = builder.build()?;
+    /// let registered = app.register("https://example.social")?;
+    /// println!("client_id: {}", registered.client_id());
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn register<I: Into<String>>(self, base_url: I) -> Result<Registered> {
+        let base = base_url.into();
+        let response = reqwest::Client::new()
+            .post(&format!("{}/api/v1/apps", base))
+            .form(&self)
+            .send()?
+            .error_for_status()?
+            .json::<Credentials>()?;
+        Ok(Registered {
+            base,
+            client_id: response.client_id,
+            client_secret: response.client_secret,
+            redirect_uri: self.redirect_uris,
+            scopes: self.scopes,
+        })
     }
 }
 
@@ -68,7 +105,7 @@ impl<'a> AppBuilder<'a> {
 
     /// Permission scope of the application.
     ///
-    /// IF none is specified, the default is Scopes::Read
+    /// IF none is specified, the default is `Scopes::read()`
     pub fn scopes(&mut self, scopes: Scopes) -> &mut Self {
         self.scopes = Some(scopes);
         self
@@ -80,6 +117,14 @@ impl<'a> AppBuilder<'a> {
         self
     }
 
+    /// Attempts to convert this build into an `App`, then registers it with
+    /// the instance at `base_url`.
+    ///
+    /// Shorthand for `self.build()?.register(base_url)`.
+    pub fn build_and_register<I: Into<String>>(self, base_url: I) -> Result<Registered> {
+        self.build()?.register(base_url)
+    }
+
     /// Attempts to convert this build into an `App`
     ///
     /// Will fail if no `client_name` was provided
@@ -93,7 +138,7 @@ impl<'a> AppBuilder<'a> {
                 .redirect_uris
                 .unwrap_or_else(|| "urn:ietf:wg:oauth:2.0:oob".into())
                 .into(),
-            scopes: self.scopes.unwrap_or_else(|| Scopes::Read),
+            scopes: self.scopes.unwrap_or_else(Scopes::read),
             website: self.website.map(|s| s.into()),
         })
     }
@@ -115,56 +160,450 @@ impl<'a> TryInto<App> for AppBuilder<'a> {
     }
 }
 
-/// Permission scope of the application.
+/// The raw `client_id`/`client_secret` pair returned by a successful
+/// `POST /api/v1/apps` call.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Credentials {
+    client_id: String,
+    client_secret: String,
+}
+
+/// An `App` that has been registered with a Mastodon instance and is ready
+/// to be used for the OAuth authorization-code flow.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Registered {
+    base: String,
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+    scopes: Scopes,
+}
+
+impl Registered {
+    /// The base URL of the instance this `App` was registered with.
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// The `client_id` issued by the instance.
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    /// The `client_secret` issued by the instance.
+    pub fn client_secret(&self) -> &str {
+        &self.client_secret
+    }
+
+    /// The `redirect_uri` this app was registered with.
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// The scopes this app was registered with.
+    pub fn scopes(&self) -> Scopes {
+        self.scopes.clone()
+    }
+
+    /// The URL the user should be sent to in order to authorize this app,
+    /// after which they'll be redirected back to `redirect_uri` with an
+    /// authorization `code` to exchange for an access token.
+    pub fn authorize_url(&self) -> String {
+        format!(
+            "{}/oauth/authorize?client_id={}&redirect_uri={}&response_type=code&scope={}",
+            self.base,
+            url_encode(&self.client_id),
+            url_encode(&self.redirect_uri),
+            url_encode(&self.scopes.to_string()),
+        )
+    }
+
+    /// Like `authorize_url`, but carries a PKCE `code_challenge` so that a
+    /// native/desktop client using a real `redirect_uri` (i.e. not the
+    /// out-of-band `urn:ietf:wg:oauth:2.0:oob` URN) can authenticate
+    /// without embedding a client secret.
+    ///
+    /// Hold on to `pkce`: its `code_verifier()` needs to be sent alongside
+    /// the authorization `code` during the token exchange.
+    pub fn authorize_url_with_pkce(&self, pkce: &Pkce) -> String {
+        format!(
+            "{}&code_challenge={}&code_challenge_method=S256",
+            self.authorize_url(),
+            url_encode(pkce.code_challenge()),
+        )
+    }
+}
+
+/// Percent-encodes a single query-string value.
+///
+/// `redirect_uri`s and scope lists can contain characters (`&`, `=`, `?`,
+/// `#`, spaces) that are significant in a query string, so this must run
+/// on every query-string value interpolated into `authorize_url`'s raw
+/// `format!`; the instance's base URL is inserted as is.
+fn url_encode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+/// A PKCE ([RFC 7636](https://tools.ietf.org/html/rfc7636)) code
+/// verifier/challenge pair for the OAuth authorization-code flow.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pkce {
+    code_verifier: String,
+    code_challenge: String,
+}
+
+impl Pkce {
+    /// Generates a new, cryptographically random code verifier (128
+    /// unreserved characters) and its `S256` code challenge.
+    pub fn new() -> Pkce {
+        use rand::Rng;
+
+        const UNRESERVED: &[u8] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~";
+        let mut rng = rand::thread_rng();
+        let code_verifier: String = (0..128)
+            .map(|_| UNRESERVED[rng.gen_range(0, UNRESERVED.len())] as char)
+            .collect();
+        Pkce::from_verifier(code_verifier)
+    }
+
+    fn from_verifier(code_verifier: String) -> Pkce {
+        let code_challenge = Pkce::challenge(&code_verifier);
+        Pkce {
+            code_verifier,
+            code_challenge,
+        }
+    }
+
+    fn challenge(code_verifier: &str) -> String {
+        use sha2::{Digest, Sha256};
+        let digest = Sha256::digest(code_verifier.as_bytes());
+        base64::encode_config(&digest, base64::URL_SAFE_NO_PAD)
+    }
+
+    /// The random verifier. Keep this secret and send it alongside the
+    /// authorization `code` during the token exchange; it is never sent
+    /// in the authorization URL.
+    pub fn code_verifier(&self) -> &str {
+        &self.code_verifier
+    }
+
+    /// The `S256` challenge derived from `code_verifier`, sent in the
+    /// authorization URL.
+    pub fn code_challenge(&self) -> &str {
+        &self.code_challenge
+    }
+}
+
+impl Default for Pkce {
+    fn default() -> Self {
+        Pkce::new()
+    }
+}
+
+/// An individual OAuth permission, optionally namespaced to a single
+/// resource (e.g. `read:accounts`, `admin:write:reports`).
+///
 /// [Details on what each permission provides][1]
-/// [1]: https://github.com/tootsuite/documentation/blob/master/Using-the-API/OAuth-details.md)
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
-pub enum Scopes {
-    /// All Permissions, equivalent to `read write follow`
-    #[serde(rename = "read write follow")]
-    All,
-    /// Only permission to add and remove followers.
-    #[serde(rename = "follow")]
+/// [1]: https://docs.joinmastodon.org/api/oauth-scopes/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Scope {
+    /// `read` or `read:<resource>`
+    Read(Option<Resource>),
+    /// `write` or `write:<resource>`
+    Write(Option<Resource>),
+    /// `follow`
     Follow,
-    /// Read only permissions.
-    #[serde(rename = "read")]
-    Read,
-    /// Read & Follow permissions.
-    #[serde(rename = "read follow")]
-    ReadFollow,
-    /// Read & Write permissions.
-    #[serde(rename = "read write")]
-    ReadWrite,
-    /// Write only permissions.
-    #[serde(rename = "write")]
-    Write,
-    /// Write & Follow permissions.
-    #[serde(rename = "write follow")]
-    WriteFollow,
+    /// `push`
+    Push,
+    /// `admin:read` or `admin:read:<resource>`
+    AdminRead(Option<AdminResource>),
+    /// `admin:write` or `admin:write:<resource>`
+    AdminWrite(Option<AdminResource>),
 }
 
-impl fmt::Display for Scopes {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use self::Scopes::*;
-        write!(
-            f,
-            "{}",
-            match *self {
-                All => "read%20write%20follow",
-                Follow => "follow",
-                Read => "read",
-                ReadFollow => "read%20follow",
-                ReadWrite => "read%20write",
-                Write => "write",
-                WriteFollow => "write%20follow",
+/// A resource that `read`/`write` scopes can be namespaced to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Resource {
+    Accounts,
+    Blocks,
+    Bookmarks,
+    Favourites,
+    Filters,
+    Follows,
+    Lists,
+    Mutes,
+    Notifications,
+    Reports,
+    Search,
+    Statuses,
+}
+
+impl Resource {
+    fn as_str(self) -> &'static str {
+        use self::Resource::*;
+        match self {
+            Accounts => "accounts",
+            Blocks => "blocks",
+            Bookmarks => "bookmarks",
+            Favourites => "favourites",
+            Filters => "filters",
+            Follows => "follows",
+            Lists => "lists",
+            Mutes => "mutes",
+            Notifications => "notifications",
+            Reports => "reports",
+            Search => "search",
+            Statuses => "statuses",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Resource> {
+        use self::Resource::*;
+        Some(match s {
+            "accounts" => Accounts,
+            "blocks" => Blocks,
+            "bookmarks" => Bookmarks,
+            "favourites" => Favourites,
+            "filters" => Filters,
+            "follows" => Follows,
+            "lists" => Lists,
+            "mutes" => Mutes,
+            "notifications" => Notifications,
+            "reports" => Reports,
+            "search" => Search,
+            "statuses" => Statuses,
+            _ => return None,
+        })
+    }
+}
+
+/// A resource that `admin:read`/`admin:write` scopes can be namespaced to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AdminResource {
+    Accounts,
+    Reports,
+}
+
+impl AdminResource {
+    fn as_str(self) -> &'static str {
+        use self::AdminResource::*;
+        match self {
+            Accounts => "accounts",
+            Reports => "reports",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<AdminResource> {
+        use self::AdminResource::*;
+        Some(match s {
+            "accounts" => Accounts,
+            "reports" => Reports,
+            _ => return None,
+        })
+    }
+}
+
+macro_rules! scope_ctors {
+    ($($fn_name:ident => $variant:expr),* $(,)*) => {
+        $(
+            /// Shorthand for constructing this scope.
+            pub fn $fn_name() -> Scope {
+                $variant
             }
-        )
+        )*
+    };
+}
+
+impl Scope {
+    scope_ctors! {
+        read => Scope::Read(None),
+        read_accounts => Scope::Read(Some(Resource::Accounts)),
+        read_blocks => Scope::Read(Some(Resource::Blocks)),
+        read_bookmarks => Scope::Read(Some(Resource::Bookmarks)),
+        read_favourites => Scope::Read(Some(Resource::Favourites)),
+        read_filters => Scope::Read(Some(Resource::Filters)),
+        read_follows => Scope::Read(Some(Resource::Follows)),
+        read_lists => Scope::Read(Some(Resource::Lists)),
+        read_mutes => Scope::Read(Some(Resource::Mutes)),
+        read_notifications => Scope::Read(Some(Resource::Notifications)),
+        read_reports => Scope::Read(Some(Resource::Reports)),
+        read_search => Scope::Read(Some(Resource::Search)),
+        read_statuses => Scope::Read(Some(Resource::Statuses)),
+        write => Scope::Write(None),
+        write_accounts => Scope::Write(Some(Resource::Accounts)),
+        write_blocks => Scope::Write(Some(Resource::Blocks)),
+        write_bookmarks => Scope::Write(Some(Resource::Bookmarks)),
+        write_favourites => Scope::Write(Some(Resource::Favourites)),
+        write_filters => Scope::Write(Some(Resource::Filters)),
+        write_follows => Scope::Write(Some(Resource::Follows)),
+        write_lists => Scope::Write(Some(Resource::Lists)),
+        write_mutes => Scope::Write(Some(Resource::Mutes)),
+        write_notifications => Scope::Write(Some(Resource::Notifications)),
+        write_reports => Scope::Write(Some(Resource::Reports)),
+        write_statuses => Scope::Write(Some(Resource::Statuses)),
+        follow => Scope::Follow,
+        push => Scope::Push,
+        admin_read => Scope::AdminRead(None),
+        admin_read_accounts => Scope::AdminRead(Some(AdminResource::Accounts)),
+        admin_read_reports => Scope::AdminRead(Some(AdminResource::Reports)),
+        admin_write => Scope::AdminWrite(None),
+        admin_write_accounts => Scope::AdminWrite(Some(AdminResource::Accounts)),
+        admin_write_reports => Scope::AdminWrite(Some(AdminResource::Reports)),
     }
 }
 
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Scope::*;
+        match *self {
+            Read(None) => write!(f, "read"),
+            Read(Some(resource)) => write!(f, "read:{}", resource.as_str()),
+            Write(None) => write!(f, "write"),
+            Write(Some(resource)) => write!(f, "write:{}", resource.as_str()),
+            Follow => write!(f, "follow"),
+            Push => write!(f, "push"),
+            AdminRead(None) => write!(f, "admin:read"),
+            AdminRead(Some(resource)) => write!(f, "admin:read:{}", resource.as_str()),
+            AdminWrite(None) => write!(f, "admin:write"),
+            AdminWrite(Some(resource)) => write!(f, "admin:write:{}", resource.as_str()),
+        }
+    }
+}
+
+impl ::std::str::FromStr for Scope {
+    type Err = ScopeParseError;
+
+    fn from_str(s: &str) -> ::std::result::Result<Scope, ScopeParseError> {
+        let mut parts = s.splitn(3, ':');
+        let scope = match (parts.next(), parts.next(), parts.next()) {
+            (Some("read"), None, None) => Scope::Read(None),
+            (Some("read"), Some(resource), None) => Scope::Read(Some(
+                Resource::from_str(resource).ok_or_else(|| ScopeParseError(s.to_string()))?,
+            )),
+            (Some("write"), None, None) => Scope::Write(None),
+            (Some("write"), Some(resource), None) => Scope::Write(Some(
+                Resource::from_str(resource).ok_or_else(|| ScopeParseError(s.to_string()))?,
+            )),
+            (Some("follow"), None, None) => Scope::Follow,
+            (Some("push"), None, None) => Scope::Push,
+            (Some("admin"), Some("read"), None) => Scope::AdminRead(None),
+            (Some("admin"), Some("read"), Some(resource)) => Scope::AdminRead(Some(
+                AdminResource::from_str(resource).ok_or_else(|| ScopeParseError(s.to_string()))?,
+            )),
+            (Some("admin"), Some("write"), None) => Scope::AdminWrite(None),
+            (Some("admin"), Some("write"), Some(resource)) => Scope::AdminWrite(Some(
+                AdminResource::from_str(resource).ok_or_else(|| ScopeParseError(s.to_string()))?,
+            )),
+            _ => return Err(ScopeParseError(s.to_string())),
+        };
+        Ok(scope)
+    }
+}
+
+/// Returned when a string can't be parsed as a [`Scope`](enum.Scope.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeParseError(String);
+
+impl fmt::Display for ScopeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid oauth scope: {:?}", self.0)
+    }
+}
+
+impl ::std::error::Error for ScopeParseError {}
+
+/// A deduplicated, canonically-ordered set of [`Scope`](enum.Scope.html)s
+/// requested for an application.
+///
+/// Build one up with the `read`/`write`/`follow`/`push`/`admin_*`
+/// constructors and `and`:
+///
+/// ```
+/// use elefren::apps::{Scope, Scopes};
+///
+/// let scopes = Scopes::read().and(Scope::write_statuses());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scopes(::std::collections::BTreeSet<Scope>);
+
 impl Default for Scopes {
+    /// The default is `Scopes::read()`, matching the old `Scopes::Read`
+    /// default and `AppBuilder::build()`'s default for an unset scope.
     fn default() -> Self {
-        Scopes::Read
+        Scopes::read()
+    }
+}
+
+impl Scopes {
+    /// An empty set of scopes.
+    pub fn none() -> Scopes {
+        Scopes(::std::collections::BTreeSet::new())
+    }
+
+    /// The unnamespaced `read` scope.
+    pub fn read() -> Scopes {
+        Scopes::from_scope(Scope::Read(None))
+    }
+
+    /// The unnamespaced `write` scope.
+    pub fn write() -> Scopes {
+        Scopes::from_scope(Scope::Write(None))
+    }
+
+    /// The `follow` scope.
+    pub fn follow() -> Scopes {
+        Scopes::from_scope(Scope::Follow)
+    }
+
+    /// The `push` scope.
+    pub fn push() -> Scopes {
+        Scopes::from_scope(Scope::Push)
+    }
+
+    /// `read write follow`, equivalent to the old `Scopes::All`.
+    pub fn all() -> Scopes {
+        Scopes::read().and(Scope::write()).and(Scope::follow())
+    }
+
+    fn from_scope(scope: Scope) -> Scopes {
+        let mut set = ::std::collections::BTreeSet::new();
+        set.insert(scope);
+        Scopes(set)
+    }
+
+    /// Adds `scope` to this set, consuming and returning `self` for chaining.
+    pub fn and(mut self, scope: Scope) -> Scopes {
+        self.0.insert(scope);
+        self
+    }
+}
+
+impl fmt::Display for Scopes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scopes: Vec<String> = self.0.iter().map(|scope| scope.to_string()).collect();
+        write!(f, "{}", scopes.join(" "))
+    }
+}
+
+impl ::std::str::FromStr for Scopes {
+    type Err = ScopeParseError;
+
+    fn from_str(s: &str) -> ::std::result::Result<Scopes, ScopeParseError> {
+        let mut set = ::std::collections::BTreeSet::new();
+        for part in s.split_whitespace() {
+            set.insert(part.parse()?);
+        }
+        Ok(Scopes(set))
+    }
+}
+
+impl ::serde::ser::Serialize for Scopes {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::ser::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
     }
 }
 
@@ -181,9 +620,9 @@ mod tests {
     #[test]
     fn test_app_scopes() {
         let mut builder = App::builder();
-        builder.client_name("test").scopes(Scopes::All);
+        builder.client_name("test").scopes(Scopes::all());
         let app = builder.build().expect("Couldn't build App");
-        assert_eq!(app.scopes(), Scopes::All);
+        assert_eq!(app.scopes(), Scopes::all());
     }
 
     #[test]
@@ -191,7 +630,7 @@ mod tests {
         let mut builder = AppBuilder::new();
         builder.client_name("foo-test");
         builder.redirect_uris("http://example.com");
-        builder.scopes(Scopes::ReadWrite);
+        builder.scopes(Scopes::read().and(Scope::write()));
         builder.website("https://example.com");
         let app = builder.build().expect("Couldn't build App");
         assert_eq!(
@@ -199,7 +638,7 @@ mod tests {
             App {
                 client_name: "foo-test".to_string(),
                 redirect_uris: "http://example.com".to_string(),
-                scopes: Scopes::ReadWrite,
+                scopes: Scopes::read().and(Scope::write()),
                 website: Some("https://example.com".to_string()),
             }
         );
@@ -218,7 +657,7 @@ mod tests {
         builder
             .website("https://example.com")
             .redirect_uris("https://example.com")
-            .scopes(Scopes::All);
+            .scopes(Scopes::all());
         builder.build().expect("no client-name");
     }
 
@@ -227,7 +666,7 @@ mod tests {
         let app = App {
             client_name: "foo-test".to_string(),
             redirect_uris: "http://example.com".to_string(),
-            scopes: Scopes::All,
+            scopes: Scopes::all(),
             website: None,
         };
         let expected = app.clone();
@@ -241,11 +680,11 @@ mod tests {
         builder
             .client_name("foo-test")
             .redirect_uris("http://example.com")
-            .scopes(Scopes::All);
+            .scopes(Scopes::all());
         let expected = App {
             client_name: "foo-test".to_string(),
             redirect_uris: "http://example.com".to_string(),
-            scopes: Scopes::All,
+            scopes: Scopes::all(),
             website: None,
         };
         let result = builder
@@ -255,38 +694,103 @@ mod tests {
     }
 
     #[test]
-    fn test_scopes_display() {
+    fn test_scopes_display_round_trip() {
         let values = [
-            Scopes::All,
-            Scopes::Follow,
-            Scopes::Read,
-            Scopes::ReadFollow,
-            Scopes::ReadWrite,
-            Scopes::Write,
-            Scopes::WriteFollow,
-        ];
-
-        let expecteds = [
-            "read%20write%20follow".to_string(),
-            "follow".to_string(),
-            "read".to_string(),
-            "read%20follow".to_string(),
-            "read%20write".to_string(),
-            "write".to_string(),
-            "write%20follow".to_string(),
+            Scopes::all(),
+            Scopes::follow(),
+            Scopes::read(),
+            Scopes::read().and(Scope::follow()),
+            Scopes::read().and(Scope::write()),
+            Scopes::write(),
+            Scopes::write().and(Scope::follow()),
+            Scopes::none().and(Scope::read_accounts()).and(Scope::write_statuses()),
         ];
 
-        let tests = values.into_iter().zip(expecteds.into_iter());
-
-        for (value, expected) in tests {
-            let result = value.to_string();
-            assert_eq!(&result, expected);
+        for value in &values {
+            let displayed = value.to_string();
+            let parsed: Scopes = displayed.parse().expect("failed to parse scopes");
+            assert_eq!(&parsed, value);
         }
     }
 
+    #[test]
+    fn test_scopes_serialize_uses_real_spaces() {
+        let scopes = Scopes::all();
+        let json = ::serde_json::to_string(&scopes).expect("failed to serialize");
+        assert_eq!(json, "\"read write follow\"");
+    }
+
     #[test]
     fn test_scopes_default() {
         let default: Scopes = Default::default();
-        assert_eq!(default, Scopes::Read);
+        assert_eq!(default, Scopes::read());
+    }
+
+    #[test]
+    fn test_registered_authorize_url() {
+        let registered = Registered {
+            base: "https://example.social".to_string(),
+            client_id: "the-client-id".to_string(),
+            client_secret: "the-client-secret".to_string(),
+            redirect_uri: "urn:ietf:wg:oauth:2.0:oob".to_string(),
+            scopes: Scopes::read(),
+        };
+        assert_eq!(
+            registered.authorize_url(),
+            "https://example.social/oauth/authorize?client_id=the-client-id&redirect_uri=urn%3Aietf%3Awg%3Aoauth%3A2.0%3Aoob&response_type=code&scope=read"
+        );
+    }
+
+    #[test]
+    fn test_registered_authorize_url_encodes_multi_scope_spaces() {
+        let registered = Registered {
+            base: "https://example.social".to_string(),
+            client_id: "the-client-id".to_string(),
+            client_secret: "the-client-secret".to_string(),
+            redirect_uri: "urn:ietf:wg:oauth:2.0:oob".to_string(),
+            scopes: Scopes::all(),
+        };
+        assert!(registered.authorize_url().contains("scope=read+write+follow"));
+    }
+
+    #[test]
+    fn test_pkce_challenge_matches_rfc7636_test_vector() {
+        // https://tools.ietf.org/html/rfc7636#appendix-B
+        let pkce = Pkce::from_verifier(
+            "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string(),
+        );
+        assert_eq!(
+            pkce.code_challenge(),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_pkce_new_generates_valid_verifier() {
+        let pkce = Pkce::new();
+        assert_eq!(pkce.code_verifier().len(), 128);
+        assert!(pkce
+            .code_verifier()
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_' || c == '~'));
+        assert_ne!(Pkce::new().code_verifier(), pkce.code_verifier());
+    }
+
+    #[test]
+    fn test_registered_authorize_url_with_pkce() {
+        let registered = Registered {
+            base: "https://example.social".to_string(),
+            client_id: "the-client-id".to_string(),
+            client_secret: "the-client-secret".to_string(),
+            redirect_uri: "https://example.com/callback".to_string(),
+            scopes: Scopes::read(),
+        };
+        let pkce = Pkce::from_verifier(
+            "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string(),
+        );
+        assert_eq!(
+            registered.authorize_url_with_pkce(&pkce),
+            "https://example.social/oauth/authorize?client_id=the-client-id&redirect_uri=https%3A%2F%2Fexample.com%2Fcallback&response_type=code&scope=read&code_challenge=E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM&code_challenge_method=S256"
+        );
     }
 }